@@ -1,7 +1,716 @@
 use std::ops::RangeBounds;
 
 use primitive_types::U256;
-use num_bigint::BigUint;
+
+// Multiplies two 256-bit values into a full 512-bit product using schoolbook
+// multiply-accumulate over the 4x64-bit limbs `U256` is stored as, so MULMOD
+// doesn't need to pull in an arbitrary-precision crate.
+fn u256_mul_wide(a: U256, b: U256) -> [u64; 8] {
+    let a_limbs = a.0;
+    let b_limbs = b.0;
+    let mut acc = [0u64; 8];
+
+    for i in 0..4 {
+        let mut carry: u128 = 0;
+        for j in 0..4 {
+            let prod = (a_limbs[i] as u128) * (b_limbs[j] as u128) + acc[i + j] as u128 + carry;
+            acc[i + j] = prod as u64;
+            carry = prod >> 64;
+        }
+        acc[i + 4] = acc[i + 4].wrapping_add(carry as u64);
+    }
+
+    acc
+}
+
+// Reduces a 512-bit value (given as 8 little-endian u64 limbs) modulo a 256-bit
+// `n` via bitwise long division: shift the remainder left one bit at a time,
+// bring in the next bit of the dividend, and subtract `n` whenever it fits.
+fn wide_mod(wide: [u64; 8], n: U256) -> U256 {
+    if n.is_zero() {
+        return U256::zero();
+    }
+
+    let mut r = U256::zero();
+    for i in (0..512).rev() {
+        r <<= 1;
+        let bit = (wide[i / 64] >> (i % 64)) & 1;
+        if bit == 1 {
+            r = r | U256::one();
+        }
+        if r >= n {
+            r -= n;
+        }
+    }
+
+    r
+}
+
+// Computes `(a + b) % n` without losing the carry bit that `a.overflowing_add(b)`
+// would otherwise truncate, by feeding the 257-bit sum through `wide_mod`.
+fn u256_addmod(a: U256, b: U256, n: U256) -> U256 {
+    if n.is_zero() {
+        return U256::zero();
+    }
+
+    let (sum, carry) = a.overflowing_add(b);
+    let mut wide = [0u64; 8];
+    wide[0..4].copy_from_slice(&sum.0);
+    wide[4] = carry as u64;
+
+    wide_mod(wide, n)
+}
+
+// Montgomery modular-multiplication context for a fixed odd 256-bit modulus `n`
+// (with `R = 2^256`). Used by the bn128 precompiles, where repeated
+// modmuls dominate and `wide_mod`'s 256-iteration long division per multiply is
+// too slow. Requires `n` odd, since that's the only case `n_prime` exists for;
+// callers with an even modulus should fall back to `wide_mod`.
+struct Montgomery {
+    n: U256,
+    n_prime: u64,
+    r2: U256,
+}
+
+impl Montgomery {
+    fn new(n: U256) -> Self {
+        assert!(n.0[0] & 1 == 1, "Montgomery modulus must be odd");
+        Montgomery {
+            n,
+            n_prime: Self::inv_neg_mod_2_64(n.0[0]),
+            r2: Self::compute_r2(n),
+        }
+    }
+
+    // Newton's iteration converges quadratically on `n^-1 mod 2^64` starting
+    // from the correct low 3 bits; negate to get `n_prime = -n^-1 mod 2^64`.
+    fn inv_neg_mod_2_64(n0: u64) -> u64 {
+        let mut inv = n0;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(n0.wrapping_mul(inv)));
+        }
+        inv.wrapping_neg()
+    }
+
+    // `R^2 mod n` computed by doubling 1 mod n 512 times, reusing `u256_addmod`
+    // instead of needing a 513-bit wide-reduction path.
+    fn compute_r2(n: U256) -> U256 {
+        let mut r = U256::one() % n;
+        for _ in 0..512 {
+            r = u256_addmod(r, r, n);
+        }
+        r
+    }
+
+    // CIOS (Coarsely Integrated Operand Scanning) Montgomery multiplication:
+    // interleaves the `a * b` accumulation with the `m * n` reduction so the
+    // running total stays 5 limbs wide instead of growing to 8.
+    fn mulmont(&self, a: U256, b: U256) -> U256 {
+        let a_limbs = a.0;
+        let b_limbs = b.0;
+        let n_limbs = self.n.0;
+        let mut t = [0u64; 5];
+
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let prod = (a_limbs[j] as u128) * (b_limbs[i] as u128) + t[j] as u128 + carry;
+                t[j] = prod as u64;
+                carry = prod >> 64;
+            }
+            let sum = t[4] as u128 + carry;
+            t[4] = sum as u64;
+            let mut top_carry = (sum >> 64) as u64;
+
+            let m = t[0].wrapping_mul(self.n_prime);
+
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let prod = (m as u128) * (n_limbs[j] as u128) + t[j] as u128 + carry;
+                t[j] = prod as u64;
+                carry = prod >> 64;
+            }
+            let sum = t[4] as u128 + carry + top_carry as u128;
+            t[4] = sum as u64;
+            top_carry = (sum >> 64) as u64;
+
+            // Divide the running total by 2^64 (drop the now-zero low limb).
+            for j in 0..4 {
+                t[j] = t[j + 1];
+            }
+            t[4] = top_carry;
+        }
+
+        let mut result = U256(t[0..4].try_into().unwrap());
+        if t[4] != 0 || result >= self.n {
+            result = result.overflowing_sub(self.n).0;
+        }
+        result
+    }
+
+    fn to_mont(&self, x: U256) -> U256 {
+        self.mulmont(x, self.r2)
+    }
+
+    fn from_mont(&self, x: U256) -> U256 {
+        self.mulmont(x, U256::one())
+    }
+}
+
+// Arbitrary-precision unsigned big integer used by the MODEXP precompile:
+// RSA-style moduli run to thousands of bits, so the fixed-width `U256`/
+// `Montgomery` types used elsewhere in this file don't fit. Little-endian
+// `u64` limbs, trimmed so the top limb is never a redundant zero (except for
+// the value zero itself, which is a single zero limb).
+#[derive(Clone)]
+struct BigUintLimbs(Vec<u64>);
+
+impl BigUintLimbs {
+    fn zero() -> Self {
+        BigUintLimbs(vec![0])
+    }
+
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        let mut limbs = vec![0u64; bytes.len().div_ceil(8).max(1)];
+        for (i, &byte) in bytes.iter().rev().enumerate() {
+            limbs[i / 8] |= (byte as u64) << ((i % 8) * 8);
+        }
+        let mut v = BigUintLimbs(limbs);
+        v.trim();
+        v
+    }
+
+    fn trim(&mut self) {
+        while self.0.len() > 1 && *self.0.last().unwrap() == 0 {
+            self.0.pop();
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|&limb| limb == 0)
+    }
+
+    fn bit_len(&self) -> usize {
+        let top = self.0.len() - 1;
+        64 * top + (64 - self.0[top].leading_zeros() as usize)
+    }
+
+    fn bit(&self, i: usize) -> u64 {
+        match self.0.get(i / 64) {
+            Some(limb) => (limb >> (i % 64)) & 1,
+            None => 0,
+        }
+    }
+
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let len = self.0.len().max(other.0.len());
+        for i in (0..len).rev() {
+            let a = self.0.get(i).copied().unwrap_or(0);
+            let b = other.0.get(i).copied().unwrap_or(0);
+            if a != b {
+                return a.cmp(&b);
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
+
+    fn shl1(&mut self) {
+        let mut carry = 0u64;
+        for limb in self.0.iter_mut() {
+            let next_carry = *limb >> 63;
+            *limb = (*limb << 1) | carry;
+            carry = next_carry;
+        }
+        if carry != 0 {
+            self.0.push(carry);
+        }
+    }
+
+    fn set_bit0(&mut self, bit: u64) {
+        if bit != 0 {
+            self.0[0] |= 1;
+        }
+    }
+
+    fn sub_assign(&mut self, other: &Self) {
+        let mut borrow: i128 = 0;
+        for i in 0..self.0.len() {
+            let rhs = other.0.get(i).copied().unwrap_or(0) as i128;
+            let mut diff = self.0[i] as i128 - rhs - borrow;
+            if diff < 0 {
+                diff += 1i128 << 64;
+                borrow = 1;
+            } else {
+                borrow = 0;
+            }
+            self.0[i] = diff as u64;
+        }
+        self.trim();
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut acc = vec![0u64; self.0.len() + other.0.len()];
+        for (i, &ai) in self.0.iter().enumerate() {
+            let mut carry: u128 = 0;
+            for (j, &bj) in other.0.iter().enumerate() {
+                let prod = (ai as u128) * (bj as u128) + acc[i + j] as u128 + carry;
+                acc[i + j] = prod as u64;
+                carry = prod >> 64;
+            }
+            acc[i + other.0.len()] = acc[i + other.0.len()].wrapping_add(carry as u64);
+        }
+        let mut v = BigUintLimbs(acc);
+        v.trim();
+        v
+    }
+
+    // Bitwise long division remainder, the same shift-compare-subtract scheme
+    // as `wide_mod` above, generalized to an arbitrary bit width.
+    fn rem(&self, modulus: &Self) -> Self {
+        let mut r = BigUintLimbs::zero();
+        for i in (0..self.bit_len()).rev() {
+            r.shl1();
+            r.set_bit0(self.bit(i));
+            if r.cmp(modulus) != std::cmp::Ordering::Less {
+                r.sub_assign(modulus);
+            }
+        }
+        r
+    }
+
+    fn to_be_bytes(&self, len: usize) -> Vec<u8> {
+        let mut out = vec![0u8; len];
+        for pos in 0..len {
+            let limb = pos / 8;
+            let shift = (pos % 8) * 8;
+            let byte = self.0.get(limb).copied().unwrap_or(0).wrapping_shr(shift as u32) as u8;
+            out[len - 1 - pos] = byte;
+        }
+        out
+    }
+}
+
+// MODEXP precompile (address 0x05): `base^exp mod modulus` over arbitrary-width
+// operands. `input` is the ABI-encoded precompile calldata: three 32-byte
+// big-endian length headers (`Bsize`, `Esize`, `Msize`) followed by the base,
+// exponent, and modulus bytes in turn, zero-extended if the input runs short.
+// Wiring this up to CALL/STATICCALL address dispatch is left for when those
+// opcodes land in the interpreter; this is the standalone precompile body.
+pub fn modexp_precompile(input: &[u8]) -> Vec<u8> {
+    fn header(input: &[u8], offset: usize) -> usize {
+        let mut bytes = [0u8; 32];
+        for i in 0..32 {
+            if offset + i < input.len() {
+                bytes[i] = input[offset + i];
+            }
+        }
+        U256::from_big_endian(&bytes).as_usize()
+    }
+
+    fn segment(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+        let mut out = vec![0u8; len];
+        for i in 0..len {
+            if offset + i < input.len() {
+                out[i] = input[offset + i];
+            }
+        }
+        out
+    }
+
+    let b_size = header(input, 0);
+    let e_size = header(input, 32);
+    let m_size = header(input, 64);
+
+    if m_size == 0 {
+        return Vec::new();
+    }
+
+    let base_bytes = segment(input, 96, b_size);
+    let exp_bytes = segment(input, 96 + b_size, e_size);
+    let mod_bytes = segment(input, 96 + b_size + e_size, m_size);
+
+    let modulus = BigUintLimbs::from_be_bytes(&mod_bytes);
+    if modulus.is_zero() {
+        return vec![0u8; m_size];
+    }
+
+    let base = BigUintLimbs::from_be_bytes(&base_bytes).rem(&modulus);
+    let exponent = BigUintLimbs::from_be_bytes(&exp_bytes);
+
+    // acc = 1 mod modulus (collapses to 0 when modulus == 1, as required)
+    let mut acc = BigUintLimbs::from_be_bytes(&[1]).rem(&modulus);
+
+    for i in (0..exponent.bit_len().max(1)).rev() {
+        acc = acc.mul(&acc).rem(&modulus);
+        if exponent.bit(i) == 1 {
+            acc = acc.mul(&base).rem(&modulus);
+        }
+    }
+
+    acc.to_be_bytes(m_size)
+}
+
+// Field element of Fp for the bn128 (alt_bn128) curve used by the ECADD/ECMUL
+// precompiles. Held in Montgomery form (relative to the `Bn128Field` that
+// produced it) so the many multiplies in point addition/doubling and the
+// Fermat inverse skip `wide_mod`'s long division.
+#[derive(Clone, Copy)]
+struct Fp {
+    mont: U256,
+}
+
+// `y^2 = x^3 + 3` over Fp, p = 21888242871839275222246405745257275088696311157297823662689037894645226208583.
+struct Bn128Field {
+    p: U256,
+    ctx: Montgomery,
+}
+
+impl Bn128Field {
+    fn new() -> Self {
+        let p = U256::from_dec_str(
+            "21888242871839275222246405745257275088696311157297823662689037894645226208583",
+        )
+        .unwrap();
+        Bn128Field {
+            p,
+            ctx: Montgomery::new(p),
+        }
+    }
+
+    fn from_u256(&self, x: U256) -> Fp {
+        Fp {
+            mont: self.ctx.to_mont(x % self.p),
+        }
+    }
+
+    fn to_u256(&self, x: Fp) -> U256 {
+        self.ctx.from_mont(x.mont)
+    }
+
+    fn add(&self, a: Fp, b: Fp) -> Fp {
+        // Montgomery form is linear in the represented value, so plain addmod
+        // on the Montgomery-form limbs is still correct.
+        Fp {
+            mont: u256_addmod(a.mont, b.mont, self.p),
+        }
+    }
+
+    fn sub(&self, a: Fp, b: Fp) -> Fp {
+        if a.mont >= b.mont {
+            Fp { mont: a.mont - b.mont }
+        } else {
+            Fp {
+                mont: self.p - (b.mont - a.mont),
+            }
+        }
+    }
+
+    fn mul(&self, a: Fp, b: Fp) -> Fp {
+        Fp {
+            mont: self.ctx.mulmont(a.mont, b.mont),
+        }
+    }
+
+    fn eq(&self, a: Fp, b: Fp) -> bool {
+        a.mont == b.mont
+    }
+
+    // Fermat's little theorem: a^(p-2) mod p, via right-to-left square-and-multiply.
+    fn inv(&self, a: Fp) -> Fp {
+        let exp = self.p - U256::from(2);
+        let mut result = self.from_u256(U256::one());
+        let mut base = a;
+        for i in 0..256 {
+            if (exp >> i) & U256::one() == U256::one() {
+                result = self.mul(result, base);
+            }
+            base = self.mul(base, base);
+        }
+        result
+    }
+
+    fn on_curve(&self, x: Fp, y: Fp) -> bool {
+        let y2 = self.mul(y, y);
+        let x3 = self.mul(self.mul(x, x), x);
+        let three = self.from_u256(U256::from(3));
+        self.eq(y2, self.add(x3, three))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct AffinePoint {
+    x: Fp,
+    y: Fp,
+    infinity: bool,
+}
+
+#[derive(Clone, Copy)]
+struct JacobianPoint {
+    x: Fp,
+    y: Fp,
+    z: Fp,
+    infinity: bool,
+}
+
+fn ec_add_affine(field: &Bn128Field, p1: AffinePoint, p2: AffinePoint) -> AffinePoint {
+    if p1.infinity {
+        return p2;
+    }
+    if p2.infinity {
+        return p1;
+    }
+
+    if field.eq(p1.x, p2.x) {
+        if !field.eq(p1.y, p2.y) {
+            // P + (-P) = point at infinity
+            return AffinePoint {
+                x: field.from_u256(U256::zero()),
+                y: field.from_u256(U256::zero()),
+                infinity: true,
+            };
+        }
+        // Doubling: lambda = 3x^2 / 2y (curve coefficient a = 0).
+        let three_x2 = field.mul(field.from_u256(U256::from(3)), field.mul(p1.x, p1.x));
+        let two_y = field.add(p1.y, p1.y);
+        let lambda = field.mul(three_x2, field.inv(two_y));
+        let x3 = field.sub(field.mul(lambda, lambda), field.add(p1.x, p1.x));
+        let y3 = field.sub(field.mul(lambda, field.sub(p1.x, x3)), p1.y);
+        AffinePoint { x: x3, y: y3, infinity: false }
+    } else {
+        let lambda = field.mul(field.sub(p2.y, p1.y), field.inv(field.sub(p2.x, p1.x)));
+        let x3 = field.sub(field.sub(field.mul(lambda, lambda), p1.x), p2.x);
+        let y3 = field.sub(field.mul(lambda, field.sub(p1.x, x3)), p1.y);
+        AffinePoint { x: x3, y: y3, infinity: false }
+    }
+}
+
+fn ec_double_jacobian(field: &Bn128Field, p: &JacobianPoint) -> JacobianPoint {
+    if p.infinity {
+        return *p;
+    }
+    let y2 = field.mul(p.y, p.y);
+    let s = field.mul(field.from_u256(U256::from(4)), field.mul(p.x, y2));
+    let m = field.mul(field.from_u256(U256::from(3)), field.mul(p.x, p.x));
+    let x3 = field.sub(field.mul(m, m), field.add(s, s));
+    let y3 = field.sub(
+        field.mul(m, field.sub(s, x3)),
+        field.mul(field.from_u256(U256::from(8)), field.mul(y2, y2)),
+    );
+    let z3 = field.mul(field.from_u256(U256::from(2)), field.mul(p.y, p.z));
+    JacobianPoint { x: x3, y: y3, z: z3, infinity: false }
+}
+
+fn ec_add_mixed_jacobian(field: &Bn128Field, p: &JacobianPoint, q: &AffinePoint) -> JacobianPoint {
+    if p.infinity {
+        return JacobianPoint {
+            x: q.x,
+            y: q.y,
+            z: field.from_u256(U256::one()),
+            infinity: q.infinity,
+        };
+    }
+    if q.infinity {
+        return *p;
+    }
+
+    let z1z1 = field.mul(p.z, p.z);
+    let u2 = field.mul(q.x, z1z1);
+    let s2 = field.mul(q.y, field.mul(p.z, z1z1));
+
+    if field.eq(p.x, u2) {
+        if !field.eq(p.y, s2) {
+            return JacobianPoint {
+                x: field.from_u256(U256::zero()),
+                y: field.from_u256(U256::one()),
+                z: field.from_u256(U256::zero()),
+                infinity: true,
+            };
+        }
+        return ec_double_jacobian(field, p);
+    }
+
+    let h = field.sub(u2, p.x);
+    let hh = field.mul(h, h);
+    let hhh = field.mul(h, hh);
+    let r = field.sub(s2, p.y);
+    let v = field.mul(p.x, hh);
+    let x3 = field.sub(field.sub(field.mul(r, r), hhh), field.add(v, v));
+    let y3 = field.sub(field.mul(r, field.sub(v, x3)), field.mul(p.y, hhh));
+    let z3 = field.mul(p.z, h);
+    JacobianPoint { x: x3, y: y3, z: z3, infinity: false }
+}
+
+fn jacobian_to_affine(field: &Bn128Field, p: &JacobianPoint) -> AffinePoint {
+    if p.infinity {
+        return AffinePoint {
+            x: field.from_u256(U256::zero()),
+            y: field.from_u256(U256::zero()),
+            infinity: true,
+        };
+    }
+    let z_inv = field.inv(p.z);
+    let z_inv2 = field.mul(z_inv, z_inv);
+    let z_inv3 = field.mul(z_inv2, z_inv);
+    AffinePoint {
+        x: field.mul(p.x, z_inv2),
+        y: field.mul(p.y, z_inv3),
+        infinity: false,
+    }
+}
+
+fn ec_mul(field: &Bn128Field, point: AffinePoint, scalar: U256) -> AffinePoint {
+    let mut acc = JacobianPoint {
+        x: field.from_u256(U256::zero()),
+        y: field.from_u256(U256::one()),
+        z: field.from_u256(U256::zero()),
+        infinity: true,
+    };
+
+    for i in (0..256).rev() {
+        acc = ec_double_jacobian(field, &acc);
+        if (scalar >> i) & U256::one() == U256::one() {
+            acc = ec_add_mixed_jacobian(field, &acc, &point);
+        }
+    }
+
+    jacobian_to_affine(field, &acc)
+}
+
+fn read_word(input: &[u8], offset: usize) -> U256 {
+    let mut bytes = [0u8; 32];
+    for i in 0..32 {
+        if offset + i < input.len() {
+            bytes[i] = input[offset + i];
+        }
+    }
+    U256::from_big_endian(&bytes)
+}
+
+// Parses a bn128 affine point from raw (x, y) words, rejecting coordinates
+// `>= p` and points that don't satisfy the curve equation. `(0, 0)` is the
+// point at infinity by convention and is accepted without an on-curve check.
+fn parse_bn128_point(field: &Bn128Field, x: U256, y: U256) -> Option<AffinePoint> {
+    if x >= field.p || y >= field.p {
+        return None;
+    }
+    if x.is_zero() && y.is_zero() {
+        return Some(AffinePoint {
+            x: field.from_u256(U256::zero()),
+            y: field.from_u256(U256::zero()),
+            infinity: true,
+        });
+    }
+    let point = AffinePoint {
+        x: field.from_u256(x),
+        y: field.from_u256(y),
+        infinity: false,
+    };
+    if field.on_curve(point.x, point.y) {
+        Some(point)
+    } else {
+        None
+    }
+}
+
+fn encode_bn128_point(field: &Bn128Field, p: AffinePoint) -> Vec<u8> {
+    let mut out = vec![0u8; 64];
+    if !p.infinity {
+        field.to_u256(p.x).to_big_endian(&mut out[0..32]);
+        field.to_u256(p.y).to_big_endian(&mut out[32..64]);
+    }
+    out
+}
+
+// ECADD precompile (address 0x06): adds two bn128 affine points encoded as four
+// 32-byte big-endian words `(x1, y1, x2, y2)`, returning their 64-byte sum.
+// Returns `None` (precompile failure, empty output) if either input point
+// doesn't lie on the curve.
+pub fn ecadd_precompile(input: &[u8]) -> Option<Vec<u8>> {
+    let field = Bn128Field::new();
+    let p1 = parse_bn128_point(&field, read_word(input, 0), read_word(input, 32))?;
+    let p2 = parse_bn128_point(&field, read_word(input, 64), read_word(input, 96))?;
+    Some(encode_bn128_point(&field, ec_add_affine(&field, p1, p2)))
+}
+
+// ECMUL precompile (address 0x07): scalar-multiplies a bn128 point `(x, y)` by
+// a 32-byte scalar via Jacobian double-and-add, returning the 64-byte result.
+// Returns `None` if the input point doesn't lie on the curve.
+pub fn ecmul_precompile(input: &[u8]) -> Option<Vec<u8>> {
+    let field = Bn128Field::new();
+    let point = parse_bn128_point(&field, read_word(input, 0), read_word(input, 32))?;
+    let scalar = read_word(input, 64);
+    Some(encode_bn128_point(&field, ec_mul(&field, point, scalar)))
+}
+
+// Signed 256-bit view over `U256`, two's complement. Used by the signed
+// opcodes (SDIV, SMOD, SLT, SGT, SAR) so sign extraction, negation, and the
+// `-2^255` edge case live in one place instead of being re-derived inline at
+// each call site.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct I256(U256);
+
+impl I256 {
+    fn new(value: U256) -> Self {
+        I256(value)
+    }
+
+    fn is_negative(&self) -> bool {
+        (self.0 >> 255) == U256::one()
+    }
+
+    fn neg(&self) -> Self {
+        I256((!self.0).overflowing_add(U256::one()).0)
+    }
+
+    // `-2^255`'s two's complement is itself, so this naturally saturates at
+    // the minimum value instead of overflowing.
+    fn abs(&self) -> U256 {
+        if self.is_negative() {
+            self.neg().0
+        } else {
+            self.0
+        }
+    }
+
+    fn checked_div(&self, other: &Self) -> U256 {
+        if other.0.is_zero() {
+            return U256::zero();
+        }
+        let min = U256::one() << 255;
+        if self.0 == min && other.0 == U256::MAX {
+            // -2^255 / -1 overflows a signed 256-bit result; EVM defines it as -2^255.
+            return min;
+        }
+        let result = self.abs() / other.abs();
+        if self.is_negative() != other.is_negative() {
+            I256(result).neg().0
+        } else {
+            result
+        }
+    }
+
+    fn checked_rem(&self, other: &Self) -> U256 {
+        if other.0.is_zero() {
+            return U256::zero();
+        }
+        let result = self.abs() % other.abs();
+        if self.is_negative() && result != U256::zero() {
+            I256(result).neg().0
+        } else {
+            result
+        }
+    }
+
+    // Orders by sign first, then by magnitude (larger magnitude negatives sort lower).
+    fn cmp_signed(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => std::cmp::Ordering::Less,
+            (false, true) => std::cmp::Ordering::Greater,
+            (true, true) => other.abs().cmp(&self.abs()),
+            (false, false) => self.0.cmp(&other.0),
+        }
+    }
+}
 
 struct Gas {
     current: u64
@@ -22,11 +731,49 @@ pub struct EvmResult {
     pub success: bool,
 }
 
+// EXP's dynamic gas surcharge is 50 per significant (non-leading-zero) byte of
+// the exponent operand.
+fn exp_byte_cost(exponent: U256) -> u64 {
+    let mut bytes = [0u8; 32];
+    exponent.to_big_endian(&mut bytes);
+    let significant_bytes = bytes.iter().position(|&b| b != 0).map(|i| 32 - i).unwrap_or(0);
+    50 * significant_bytes as u64
+}
+
+// Looks up how many stack items an opcode needs and how much gas it costs,
+// or `None` for an undefined opcode. `stack` is only consulted for EXP's
+// dynamic per-exponent-byte surcharge.
+fn opcode_info(opcode: u8, stack: &[U256]) -> Option<(usize, u64)> {
+    if (0x5F..=0x7F).contains(&opcode) {
+        return Some((0, 3)); // PUSH0..PUSH32
+    }
+
+    match opcode {
+        0x00 => Some((0, 0)),                             // STOP
+        0x50 => Some((1, 2)),                             // POP
+        0x01 | 0x03 => Some((2, 3)),                      // ADD/SUB
+        0x02 => Some((2, 5)),                             // MUL
+        0x04 | 0x05 | 0x06 | 0x07 => Some((2, 5)),        // DIV/SDIV/MOD/SMOD
+        0x08 | 0x09 => Some((3, 8)),                      // ADDMOD/MULMOD
+        0x0A => {
+            let exponent = stack.get(1).copied().unwrap_or(U256::zero());
+            Some((2, 10 + exp_byte_cost(exponent)))
+        }
+        0x0B => Some((2, 5)),                             // SIGNEXTEND
+        0x10 | 0x11 | 0x12 | 0x13 | 0x14 => Some((2, 3)), // LT/GT/SLT/SGT/EQ
+        0x15 | 0x19 => Some((1, 3)),                      // ISZERO/NOT
+        0x16 | 0x17 | 0x18 => Some((2, 3)),                // AND/OR/XOR
+        0x1B | 0x1C | 0x1D => Some((2, 3)),                // SHL/SHR/SAR
+        _ => None,
+    }
+}
+
 pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
     let mut stack: Vec<U256> = Vec::new();
     let mut pc = 0;
     let mut stop_flag = false;
-    let _gas: Gas = Gas {
+    let mut success = true;
+    let mut gas: Gas = Gas {
         current: 100000000
     };
 
@@ -36,6 +783,31 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
         let opcode = code[pc];
         pc += 1;
 
+        // Undefined opcode: halt cleanly instead of falling through as a no-op.
+        let (stack_needed, cost) = match opcode_info(opcode, &stack) {
+            Some(info) => info,
+            None => {
+                success = false;
+                stop_flag = true;
+                continue;
+            }
+        };
+
+        // Short stack: halt cleanly instead of panicking in `stack.remove`.
+        if stack.len() < stack_needed {
+            success = false;
+            stop_flag = true;
+            continue;
+        }
+
+        // Out of gas: halt cleanly per EVM out-of-gas semantics.
+        if gas.current < cost {
+            success = false;
+            stop_flag = true;
+            continue;
+        }
+        gas.decrement(&cost);
+
         // STOP
         if opcode == 0x00 {
             stop_flag = true;
@@ -119,11 +891,10 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
             let n = stack.remove(2);
             let a = stack.remove(1);
             let b = stack.remove(0);
-            if n == U256::zero() { 
+            if n == U256::zero() {
                 stack.insert(0, U256::zero());
             } else {
-                let result = (a.overflowing_add(b).0) % n;
-                stack.insert(0, result);
+                stack.insert(0, u256_addmod(a, b, n));
             }
         }
 
@@ -132,33 +903,11 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
             let n = stack.remove(2);
             let a = stack.remove(1);
             let b = stack.remove(0);
-            if n == U256::zero() { 
+            if n == U256::zero() {
                 stack.insert(0, U256::zero());
             } else {
-                // NOTE this logic differs from ADDMOD because a.overflowing_mul(b) wasn't evaluating correctly
-                // so I imported the num_bigint library
-                let mut a_bytes = [0u8; 32];
-                let mut b_bytes = [0u8; 32];
-                let mut n_bytes = [0u8; 32];
-
-                a.to_big_endian(&mut a_bytes);
-                b.to_big_endian(&mut b_bytes);
-                n.to_big_endian(&mut n_bytes);
-
-                let a_big = BigUint::from_bytes_be(&a_bytes);
-                let b_big = BigUint::from_bytes_be(&b_bytes);
-                let n_big = BigUint::from_bytes_be(&n_bytes);
-
-                // Perform multiplication and modulo with full precision
-                let result_big = (a_big * b_big) % n_big;
-
-                // Convert back to U256
-                let result_bytes = result_big.to_bytes_be();
-                let mut result_array = [0u8; 32];
-                if result_bytes.len() <= 32 {
-                    result_array[32 - result_bytes.len()..].copy_from_slice(&result_bytes);
-                }
-                stack.insert(0, U256::from_big_endian(&result_array));
+                let product = u256_mul_wide(a, b);
+                stack.insert(0, wide_mod(product, n));
             }
         }
 
@@ -195,65 +944,18 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
 
         // SDIV
         if opcode == 0x05 {
-            let denominator = stack.remove(1); 
+            let denominator = stack.remove(1);
             let numerator = stack.remove(0);
-            
-            if denominator == U256::zero() {
-                stack.insert(0, U256::zero());
-            } else {
-                // Check if numerator is -2^255 (minimum value)
-                let min_value = U256::from(1) << 255;
-                let is_numerator_min = numerator == min_value;
-                
-                // Get signs
-                let numerator_negative = (numerator >> 255) == U256::from(1);
-                let denominator_negative = (denominator >> 255) == U256::from(1);
-                
-                // Convert to absolute values
-                let abs_numerator = if numerator_negative { (!numerator).overflowing_add(U256::from(1)).0 } else { numerator };
-                let abs_denominator = if denominator_negative { (!denominator).overflowing_add(U256::from(1)).0 } else { denominator };
-                
-                // Perform division
-                let mut result = abs_numerator / abs_denominator;
-                
-                // Handle special case: -2^255 / -1
-                if is_numerator_min && denominator == U256::MAX {
-                    result = min_value;
-                } else if numerator_negative != denominator_negative {
-                    // Result should be negative
-                    result = (!result).overflowing_add(U256::from(1)).0;
-                }
-                
-                stack.insert(0, result);
-            }
+            let result = I256::new(numerator).checked_div(&I256::new(denominator));
+            stack.insert(0, result);
         }
 
         // SMOD
         if opcode == 0x07 {
-            let denominator = stack.remove(1); 
+            let denominator = stack.remove(1);
             let numerator = stack.remove(0);
-
-            if denominator == U256::zero() {
-                stack.insert(0, U256::zero());
-            } else {
-                // Get signs by checking most significant bit
-                let numerator_negative = (numerator >> 255) == U256::from(1);
-                let denominator_negative = (denominator >> 255) == U256::from(1);
-
-                // Convert to absolute values
-                let abs_numerator = if numerator_negative { (!numerator).overflowing_add(U256::from(1)).0 } else { numerator };
-                let abs_denominator = if denominator_negative { (!denominator).overflowing_add(U256::from(1)).0 } else { denominator };
-
-                // Perform modulo on absolute values
-                let mut result = abs_numerator % abs_denominator;
-
-                // If numerator was negative, result should be negative
-                if numerator_negative && result != U256::zero() {
-                    result = (!result).overflowing_add(U256::from(1)).0;
-                }
-
-                stack.insert(0, result);
-            }
+            let result = I256::new(numerator).checked_rem(&I256::new(denominator));
+            stack.insert(0, result);
         }
 
         // LT
@@ -270,48 +972,20 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
             stack.insert(0, if left_side > right_side { U256::one() } else { U256::zero() });
         }
 
-        // SLT 
+        // SLT
         if opcode == 0x12 {
             let right_side = stack.remove(1);
             let left_side = stack.remove(0);
-            
-            let left_negative = (left_side >> 255) == U256::from(1);
-            let right_negative = (right_side >> 255) == U256::from(1);
-
-            if left_negative == right_negative { 
-                // handle same sign with absolutes
-                let abs_left = if left_negative { (!left_side).overflowing_add(U256::from(1)).0 } else { left_side };
-                let abs_right = if right_negative { (!right_side).overflowing_add(U256::from(1)).0 } else { right_side };
-
-                let result = if left_negative { abs_right < abs_left } else { abs_left < abs_right };
-
-                stack.insert(0, U256::from(result as u8));
-            } else {
-                // signs are different, convert `left_negative` bool to 1 or 0
-                stack.insert(0, U256::from(left_negative as u8));
-            }
+            let is_lt = I256::new(left_side).cmp_signed(&I256::new(right_side)) == std::cmp::Ordering::Less;
+            stack.insert(0, U256::from(is_lt as u8));
         }
 
-        // SLT 
+        // SGT
         if opcode == 0x13 {
             let right_side = stack.remove(1);
             let left_side = stack.remove(0);
-            
-            let left_negative = (left_side >> 255) == U256::from(1);
-            let right_negative = (right_side >> 255) == U256::from(1);
-
-            if left_negative == right_negative { 
-                // handle same sign with absolutes
-                let abs_left = if left_negative { (!left_side).overflowing_add(U256::from(1)).0 } else { left_side };
-                let abs_right = if right_negative { (!right_side).overflowing_add(U256::from(1)).0 } else { right_side };
-
-                let result = if left_negative { abs_right > abs_left } else { abs_left > abs_right };
-
-                stack.insert(0, U256::from(result as u8));
-            } else {
-                // signs are different, convert `left_negative` bool to 1 or 0
-                stack.insert(0, U256::from(!left_negative as u8));
-            }
+            let is_gt = I256::new(left_side).cmp_signed(&I256::new(right_side)) == std::cmp::Ordering::Greater;
+            stack.insert(0, U256::from(is_gt as u8));
         }
 
         // EQ 
@@ -372,10 +1046,8 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
         if opcode == 0x1D {
             let value = stack.remove(1);
             let shift = stack.remove(0);
+            let value_negative = I256::new(value).is_negative();
 
-            // Check if the input value is negative (MSB is 1)
-            let value_negative = (value >> 255) == U256::from(1);
-            
             if shift > U256::from(255) {
                 // If shift is > 255, result is 0 for positive numbers
                 // or all 1s (-1) for negative numbers
@@ -383,14 +1055,14 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
             } else {
                 let shift = shift.as_u32();
                 let mut result = value >> shift;
-                
+
                 // For negative numbers, we need to fill the upper bits with 1s
                 if value_negative {
                     // Create a mask with 1s in the positions we shifted
                     let mask = (!U256::zero()) << (256 - shift);
                     result = result | mask;
                 }
-                
+
                 stack.insert(0, result);
             }
         }
@@ -398,8 +1070,5 @@ pub fn evm(_code: impl AsRef<[u8]>) -> EvmResult {
 
 
 
-    EvmResult {
-        stack,
-        success: true,
-    }
+    EvmResult { stack, success }
 }